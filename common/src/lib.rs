@@ -0,0 +1,140 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Helpers shared by the rp-hal and embassy demo binaries.
+
+#![no_std]
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use ssd1351::{interface::DisplayInterface, mode::GraphicsMode};
+
+/// SPI clock for the display. The SSD1351 datasheet specifies a minimum
+/// serial clock cycle time of 50 ns, so 20 MHz is the fastest it supports.
+pub const DISPLAY_SPI_FREQ_HZ: u32 = 20_000_000;
+
+/// Draws a line of text horizontally centred on the display with its top
+/// edge at `y`.
+///
+/// The width comes from the driver's rotation-aware `get_dimensions()`
+/// rather than `bounding_box()`, which ignores rotation.
+pub fn draw_text_centered<DI>(
+    display: &mut GraphicsMode<DI>,
+    text: &str,
+    y: i32,
+    style: MonoTextStyle<Rgb565>,
+) -> Result<(), <GraphicsMode<DI> as DrawTarget>::Error>
+where
+    DI: DisplayInterface,
+{
+    let (width, _) = display.get_dimensions();
+    Text::with_text_style(
+        text,
+        Point::new(i32::from(width) / 2, y),
+        style,
+        TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Top)
+            .build(),
+    )
+    .draw(display)?;
+    Ok(())
+}
+
+/// This is a very simple buffer to pre format a short line of text
+/// limited to `N` bytes.
+pub struct FmtBuf<const N: usize> {
+    buf: [u8; N],
+    ptr: usize,
+}
+
+impl<const N: usize> FmtBuf<N> {
+    pub fn new() -> Self {
+        Self {
+            buf: [0; N],
+            ptr: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.ptr = 0;
+    }
+
+    pub fn as_str(&self) -> &str {
+        // write_str only ever stores whole characters, so this cannot fail
+        core::str::from_utf8(&self.buf[0..self.ptr]).unwrap_or_default()
+    }
+}
+
+impl<const N: usize> Default for FmtBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Write for FmtBuf<N> {
+    /// Writes as much of `s` as fits, returning an error if anything had
+    /// to be dropped.
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let rest_len = self.buf.len() - self.ptr;
+        let mut len = if rest_len < s.len() {
+            rest_len
+        } else {
+            s.len()
+        };
+        // Never split a multibyte character when truncating
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.buf[self.ptr..(self.ptr + len)]
+            .copy_from_slice(&s.as_bytes()[0..len]);
+        self.ptr += len;
+        if len < s.len() {
+            Err(core::fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn fmt_buf_formats_and_resets() {
+        let mut buf = FmtBuf::<64>::new();
+        write!(&mut buf, "counter: {}", 42).unwrap();
+        assert_eq!(buf.as_str(), "counter: 42");
+        buf.reset();
+        assert_eq!(buf.as_str(), "");
+    }
+
+    #[test]
+    fn fmt_buf_reports_overflow() {
+        let mut buf = FmtBuf::<8>::new();
+        assert!(write!(&mut buf, "counter: {}", 1).is_err());
+        assert_eq!(buf.as_str(), "counter:");
+    }
+
+    #[test]
+    fn fmt_buf_truncates_on_char_boundary() {
+        // Two-byte characters that overrun by a three-byte one
+        let mut buf = FmtBuf::<9>::new();
+        assert!(buf.write_str("°°°°€€").is_err());
+        assert_eq!(buf.as_str(), "°°°°");
+
+        // Three-byte characters with a one byte gap left over
+        let mut buf = FmtBuf::<7>::new();
+        assert!(buf.write_str("ab€€").is_err());
+        assert_eq!(buf.as_str(), "ab€");
+        buf.write_str("c").unwrap();
+        assert_eq!(buf.as_str(), "ab€c");
+    }
+}
@@ -16,8 +16,9 @@
 
 // For string formatting.
 use core::fmt::Write;
-use defmt::info;
+use defmt::{info, warn};
 use defmt_rtt as _;
+use demo_common::{draw_text_centered, FmtBuf, DISPLAY_SPI_FREQ_HZ};
 use embassy_executor::executor::Spawner;
 use embassy_executor::time::{Delay, Duration, Timer};
 use embassy_rp::gpio::{Level, Output};
@@ -52,7 +53,7 @@ async fn main(_spawner: Spawner, p: Peripherals) {
     let led = p.PIN_25;
 
     let mut config = spi::Config::default();
-    config.frequency = 20_000_000u32;
+    config.frequency = DISPLAY_SPI_FREQ_HZ;
     let spi = Spi::new(p.SPI0, clk, mosi, miso, config);
 
     info!("Init display");
@@ -62,9 +63,16 @@ async fn main(_spawner: Spawner, p: Peripherals) {
     let mut rst = Output::new(rst, Level::Low);
     let mut led = Output::new(led, Level::Low);
 
+    // Framebuffer for the buffered driver mode: two bytes per pixel
+    let buffer =
+        cortex_m::singleton!(: [u8; 128 * 128 * 2] = [0; 128 * 128 * 2])
+            .unwrap();
     let mut display: ssd1351::mode::graphics::GraphicsMode<_> =
-        ssd1351::builder::Builder::new().connect_spi(spi, dc).into();
-    display.init().unwrap();
+        ssd1351::builder::Builder::new()
+            .connect_spi(spi, dc, buffer)
+            .into();
+    // Pulse the reset line before sending the init sequence so that a
+    // cold panel is in a known state; a single init is then enough.
     info!("Reset display");
     display.reset(&mut rst, &mut Delay).unwrap();
     display.init().unwrap();
@@ -91,22 +99,8 @@ async fn main(_spawner: Spawner, p: Peripherals) {
         .unwrap();
 
     // Draw fixed text:
-    Text::with_baseline(
-        "Hello world!",
-        Point::new(10, 0),
-        text_style,
-        Baseline::Top,
-    )
-    .draw(&mut display)
-    .unwrap();
-    Text::with_baseline(
-        "Hello Rust!",
-        Point::new(10, 20),
-        text_style,
-        Baseline::Top,
-    )
-    .draw(&mut display)
-    .unwrap();
+    draw_text_centered(&mut display, "Hello world!", 0, text_style).unwrap();
+    draw_text_centered(&mut display, "Hello Rust!", 20, text_style).unwrap();
 
     let blue = PrimitiveStyleBuilder::new()
         // .stroke_color(Rgb565::WHITE)
@@ -115,7 +109,7 @@ async fn main(_spawner: Spawner, p: Peripherals) {
         .build();
 
     let mut count: u32 = 0;
-    let mut buf = FmtBuf::new();
+    let mut buf = FmtBuf::<64>::new();
     loop {
         led.set_high();
 
@@ -123,8 +117,11 @@ async fn main(_spawner: Spawner, p: Peripherals) {
         // display.init().unwrap();
 
         buf.reset();
-        // Format some text into a static buffer:
-        write!(&mut buf, "counter: {}", count).unwrap();
+        // Format some text into a static buffer, drawing whatever fits if
+        // it ever outgrows it:
+        if write!(&mut buf, "counter: {}", count).is_err() {
+            warn!("Counter text truncated");
+        }
         info!("Counter: {}", count);
         count += 1;
 
@@ -144,7 +141,7 @@ async fn main(_spawner: Spawner, p: Peripherals) {
         .draw(&mut display)
         .unwrap();
 
-        // display.flush().unwrap();
+        display.flush();
 
         led.set_low();
         // Wait a bit:
@@ -154,42 +151,3 @@ async fn main(_spawner: Spawner, p: Peripherals) {
         Timer::after(Duration::from_secs(1)).await;
     }
 }
-
-/// This is a very simple buffer to pre format a short line of text
-/// limited arbitrarily to 64 bytes.
-struct FmtBuf {
-    buf: [u8; 64],
-    ptr: usize,
-}
-
-impl FmtBuf {
-    fn new() -> Self {
-        Self {
-            buf: [0; 64],
-            ptr: 0,
-        }
-    }
-
-    fn reset(&mut self) {
-        self.ptr = 0;
-    }
-
-    fn as_str(&self) -> &str {
-        core::str::from_utf8(&self.buf[0..self.ptr]).unwrap()
-    }
-}
-
-impl core::fmt::Write for FmtBuf {
-    fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        let rest_len = self.buf.len() - self.ptr;
-        let len = if rest_len < s.len() {
-            rest_len
-        } else {
-            s.len()
-        };
-        self.buf[self.ptr..(self.ptr + len)]
-            .copy_from_slice(&s.as_bytes()[0..len]);
-        self.ptr += len;
-        Ok(())
-    }
-}